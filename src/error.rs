@@ -0,0 +1,52 @@
+use std::{error::Error, fmt};
+
+/// Error returned by [`crate::ShutUp::adopt`] if adopting the child would create a circular reference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CycleError;
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "adopting the handle would create a circular reference")
+    }
+}
+
+impl Error for CycleError {}
+
+/// Error returned by [`crate::ShutUpExt::or_shutdown`] if the future is cancelled by shutdown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled by shutdown")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Reason of a shutdown triggered by a [`crate::Guard`] dropped while panicking.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuardPanicked;
+
+impl fmt::Display for GuardPanicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "guard dropped while panicking")
+    }
+}
+
+impl Error for GuardPanicked {}
+
+/// Reason of a shutdown escalated by a [`crate::Supervisor`] whose children restart too often.
+#[cfg(feature = "tokio")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntensityExceeded;
+
+#[cfg(feature = "tokio")]
+impl fmt::Display for IntensityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "maximum restart intensity of supervisor exceeded")
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Error for IntensityExceeded {}
@@ -0,0 +1,82 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use crate::util::lock;
+
+/// A list of wakers notified on state changes, independent of any async runtime.
+#[derive(Clone, Default)]
+pub(crate) struct Event(Arc<Mutex<Wakers>>);
+
+#[derive(Default)]
+struct Wakers {
+    next: usize,
+    wakers: HashMap<usize, Waker>,
+}
+
+impl Event {
+    /// Wake all the futures waiting on this event.
+    ///
+    /// The state change must be made before calling this.
+    pub fn notify(&self) {
+        let wakers = {
+            let mut inner = lock(&self.0);
+            inner.wakers.drain().collect::<Vec<_>>()
+        };
+        for (_, i) in wakers {
+            i.wake();
+        }
+    }
+
+    /// Wait until the condition holds, rechecking it on every notification.
+    pub fn until<F: Fn() -> bool>(&self, cond: F) -> Until<F> {
+        Until {
+            event: self.clone(),
+            cond,
+            key: None,
+        }
+    }
+}
+
+/// Future for [`Event::until`].
+pub(crate) struct Until<F> {
+    event: Event,
+    cond: F,
+    key: Option<usize>,
+}
+
+impl<F: Fn() -> bool + Unpin> Future for Until<F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if (this.cond)() {
+            return Poll::Ready(());
+        }
+        {
+            let mut inner = lock(&this.event.0);
+            let key = *this.key.get_or_insert_with(|| {
+                inner.next += 1;
+                inner.next
+            });
+            inner.wakers.insert(key, cx.waker().clone());
+        }
+        // check again after registering, so that a notification either
+        // happens-before the check or wakes the registered waker
+        if (this.cond)() {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+impl<F> Drop for Until<F> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            lock(&self.event.0).wakers.remove(&key);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{Cancelled, ShutUp, Wait};
+
+/// Future for [`ShutUpExt::or_shutdown`].
+#[must_use = "futures do nothing unless polled"]
+pub struct OrShutdown<F> {
+    fut: Pin<Box<F>>,
+    wait: Pin<Box<dyn Wait>>,
+}
+
+impl<F: Future> Future for OrShutdown<F> {
+    type Output = Result<F::Output, Cancelled>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.wait.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Err(Cancelled));
+        }
+        self.fut.as_mut().poll(cx).map(Ok)
+    }
+}
+
+/// Extension methods for racing futures against shutdown.
+pub trait ShutUpExt: Future + Sized {
+    /// Run this future until completion or shutdown of the handle, whichever comes first.
+    ///
+    /// Returns [`Cancelled`] if the handle is shut down before this future completes.
+    /// Shutdown is checked before polling this future,
+    /// so a future that is always ready would not prevent cancellation.
+    /// The returned future does not borrow the handle.
+    fn or_shutdown(self, handle: &ShutUp) -> OrShutdown<Self>;
+}
+
+impl<F: Future> ShutUpExt for F {
+    fn or_shutdown(self, handle: &ShutUp) -> OrShutdown<Self> {
+        OrShutdown {
+            fut: Box::pin(self),
+            wait: Box::pin(handle.wait()),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::thread;
+
+use crate::{GuardPanicked, Reason, ShutUp};
+
+/// A guard that shuts down its handle when dropped.
+///
+/// Created by [`ShutUp::guard`].
+#[must_use = "the handle is shut down immediately if the guard is dropped"]
+pub struct Guard(Option<ShutUp>);
+
+impl Guard {
+    /// Drop this guard without shutting down the handle.
+    pub fn disarm(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let Some(handle) = self.0.take() else {
+            return;
+        };
+        if thread::panicking() {
+            handle.shut_with(Reason::error(GuardPanicked));
+        } else {
+            handle.shut();
+        }
+    }
+}
+
+impl ShutUp {
+    /// Create a guard that shuts down this handle when dropped, including on panic unwinding.
+    ///
+    /// When dropped while panicking, the handle is shut down with [`GuardPanicked`] as the reason,
+    /// which maps to a failing exit code, see [`Reason::exit_code`].
+    ///
+    /// Useful for taking down the subtree when a critical task dies.
+    /// Call [`Guard::disarm`] on normal exit to opt out.
+    pub fn guard(&self) -> Guard {
+        Guard(Some(self.clone()))
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    cmp::Reverse,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::HookPanic;
+
+/// A shutdown hook with ordering constraints.
+///
+/// Hooks of a handle are run in an order satisfying all [`Self::before`] and [`Self::after`] constraints,
+/// preferring hooks of higher [`Self::priority`] and then earlier registration.
+/// Constraints referring to unknown names are ignored,
+/// and constraints forming a cycle are broken deterministically by the same preference.
+pub struct Hook {
+    name: Option<String>,
+    priority: i32,
+    before: Vec<String>,
+    after: Vec<String>,
+    run: Box<dyn FnOnce() + Send>,
+}
+
+impl Hook {
+    /// Create an anonymous hook with priority `0`.
+    pub fn new(run: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            name: None,
+            priority: 0,
+            before: vec![],
+            after: vec![],
+            run: Box::new(run),
+        }
+    }
+
+    /// Name this hook, so that other hooks can refer to it.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the priority of this hook, hooks of higher priority are run first.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Run this hook before the hooks with the given name.
+    pub fn before(mut self, name: impl Into<String>) -> Self {
+        self.before.push(name.into());
+        self
+    }
+
+    /// Run this hook after the hooks with the given name.
+    pub fn after(mut self, name: impl Into<String>) -> Self {
+        self.after.push(name.into());
+        self
+    }
+
+    /// Get the name of this hook.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Run this hook, catching panics.
+    pub(crate) fn run(self) -> Result<(), HookPanic> {
+        panic::catch_unwind(AssertUnwindSafe(self.run)).map_err(|x| HookPanic::new(self.name, x))
+    }
+}
+
+/// Sort hooks in registration order into execution order.
+pub(crate) fn sort(hooks: Vec<Hook>) -> Vec<Hook> {
+    let n = hooks.len();
+    // `next[i]` are hooks that must run after hook `i`
+    let mut next = vec![vec![]; n];
+    let mut deps = vec![0usize; n];
+    for (i, a) in hooks.iter().enumerate() {
+        for (j, b) in hooks.iter().enumerate() {
+            let Some(name) = &b.name else {
+                continue;
+            };
+            if i == j {
+                continue;
+            }
+            if a.before.contains(name) {
+                next[i].push(j);
+                deps[j] += 1;
+            }
+            if a.after.contains(name) {
+                next[j].push(i);
+                deps[i] += 1;
+            }
+        }
+    }
+    let mut done = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let key = |&i: &usize| (hooks[i].priority, Reverse(i));
+    while order.len() < n {
+        let pending = || (0..n).filter(|&i| !done[i]);
+        let pick = pending()
+            .filter(|&i| deps[i] == 0)
+            .max_by_key(key)
+            .or_else(|| pending().max_by_key(key))
+            .unwrap();
+        done[pick] = true;
+        order.push(pick);
+        for &j in &next[pick] {
+            deps[j] = deps[j].saturating_sub(1);
+        }
+    }
+    let mut hooks = hooks.into_iter().map(Some).collect::<Vec<_>>();
+    order
+        .into_iter()
+        .map(|i| hooks[i].take().unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(name: &str) -> Hook {
+        Hook::new(|| {}).named(name)
+    }
+
+    fn order(hooks: Vec<Hook>) -> Vec<String> {
+        sort(hooks)
+            .iter()
+            .map(|x| x.name().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn registration_order_by_default() {
+        assert_eq!(
+            order(vec![hook("a"), hook("b"), hook("c")]),
+            ["a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn higher_priority_first() {
+        let hooks = vec![hook("a"), hook("b").priority(1), hook("c").priority(-1)];
+        assert_eq!(order(hooks), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn dependencies_override_priority() {
+        let hooks = vec![
+            hook("log").priority(10),
+            hook("metrics").before("log"),
+            hook("listener").before("metrics").priority(-10),
+        ];
+        assert_eq!(order(hooks), ["listener", "metrics", "log"]);
+        let hooks = vec![hook("a").after("b").priority(1), hook("b")];
+        assert_eq!(order(hooks), ["b", "a"]);
+    }
+
+    #[test]
+    fn unknown_names_are_ignored() {
+        let hooks = vec![hook("a").after("missing"), hook("b").before("missing")];
+        assert_eq!(order(hooks), ["a", "b"]);
+    }
+
+    #[test]
+    fn run_catches_panics() {
+        let err = Hook::new(|| panic!("boom")).named("bad").run().unwrap_err();
+        assert_eq!(err.hook.as_deref(), Some("bad"));
+        assert_eq!(err.message.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn cycles_are_broken_deterministically() {
+        let hooks = || {
+            vec![
+                hook("a").after("c"),
+                hook("b").after("a").priority(1),
+                hook("c").after("b"),
+                hook("d"),
+            ]
+        };
+        // `d` is free, then the cycle is broken at the highest priority hook
+        assert_eq!(order(hooks()), ["d", "b", "c", "a"]);
+        assert_eq!(order(hooks()), order(hooks()));
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::{Wait, event::Event};
+
+/// A level-triggered, one-time event.
+pub(crate) struct Latch {
+    status: Arc<AtomicBool>,
+    event: Event,
+}
+
+impl Latch {
+    pub fn new() -> Self {
+        Self {
+            status: Arc::new(AtomicBool::new(false)),
+            event: Event::default(),
+        }
+    }
+
+    /// Check whether this latch is set.
+    pub fn is_set(&self) -> bool {
+        self.status.load(Ordering::Acquire)
+    }
+
+    /// Set this latch, returning `false` if it was already set.
+    pub fn set(&self) -> bool {
+        if self.status.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        self.event.notify();
+        true
+    }
+
+    /// Wait until this latch is set.
+    ///
+    /// The returned future resolves immediately if this latch is already set.
+    pub fn wait(&self) -> impl Wait + use<> {
+        let status = self.status.clone();
+        self.event.until(move || status.load(Ordering::Acquire))
+    }
+}
+
+/// A counter that can be waited on to reach zero.
+#[derive(Default)]
+pub(crate) struct Counter {
+    count: Arc<AtomicUsize>,
+    event: Event,
+}
+
+impl Counter {
+    pub fn get(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    pub fn increment(&self) {
+        self.count.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn decrement(&self) {
+        if self.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.event.notify();
+        }
+    }
+
+    /// Wait until this counter reaches zero.
+    pub fn zero(&self) -> impl Wait + use<> {
+        let count = self.count.clone();
+        self.event.until(move || count.load(Ordering::Acquire) == 0)
+    }
+}
@@ -1,56 +1,372 @@
-use std::sync::{
-    Arc, LazyLock, Mutex,
-    atomic::{AtomicBool, Ordering},
+use std::{
+    collections::HashSet,
+    fmt, mem,
+    pin::Pin,
+    sync::{
+        Arc, LazyLock, Mutex, OnceLock, PoisonError, Weak,
+        atomic::{AtomicBool, AtomicU8, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use tokio::sync::broadcast;
+use crate::{
+    latch::{Counter, Latch},
+    util::{block_on, catch_unwind, join_all, lock, race},
+};
+
+mod error;
+mod event;
+mod ext;
+mod guard;
+mod hook;
+mod latch;
+mod readiness;
+mod reason;
+mod report;
+#[cfg(feature = "tokio")]
+mod run;
+#[cfg(all(unix, feature = "signal"))]
+pub mod signal;
+mod state;
+#[cfg(feature = "futures")]
+pub mod stream;
+#[cfg(feature = "tokio")]
+mod supervisor;
+mod task;
+mod tree;
+mod util;
+
+#[cfg(feature = "tokio")]
+pub use error::IntensityExceeded;
+pub use error::{Cancelled, CycleError, GuardPanicked};
+pub use ext::{OrShutdown, ShutUpExt};
+pub use guard::Guard;
+pub use hook::Hook;
+pub use readiness::Readiness;
+pub use reason::Reason;
+pub use report::{HookPanic, HookRun, ShutdownReport};
+#[cfg(feature = "tokio")]
+pub use run::{DEFAULT_DEADLINE, run_until_shutdown, run_until_shutdown_timeout};
+pub use state::{State, StateWatch};
+#[cfg(feature = "tokio")]
+pub use supervisor::{Strategy, Supervisor};
+pub use tree::Tree;
 
 pub trait Wait: Future<Output = ()> + Send + 'static {}
 
 impl<T> Wait for T where T: Future<Output = ()> + Send + 'static {}
 
+/// Phase of a shutdown handle.
+///
+/// Phases are ordered, a handle only moves forward from [`Phase::Running`] to [`Phase::Forced`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    /// The handle is not shut down.
+    Running,
+    /// Shutdown has begun, but is delayed by the pre-stop delay, see [`ShutUp::set_pre_stop`].
+    ///
+    /// The handle should stop accepting new work, while existing work continues as usual.
+    PreStop,
+    /// The handle is shut down, work should be finished gracefully.
+    Graceful,
+    /// The handle is forced to shut down, remaining work should be aborted.
+    Forced,
+}
+
+/// Strategy of tearing down the children of a handle.
+///
+/// By default, children are shut down in reverse creation order,
+/// so that the last started subsystem stops first,
+/// and their asynchronous hooks are run concurrently.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Teardown {
+    /// Shut down children in creation order instead.
+    pub fifo: bool,
+    /// Wait for the asynchronous hooks of each child to complete before running those of the next child.
+    ///
+    /// With [`ShutUp::shutdown`], each child is also signaled only after the previous one has completed,
+    /// and synchronous hooks of this handle run after all of them.
+    /// [`ShutUp::shut`] cannot wait, so it still signals all the children immediately.
+    /// If the future returned by [`ShutUp::shutdown`] is dropped early,
+    /// the remaining children are left running until they are shut down otherwise,
+    /// for example by [`ShutUp::force`].
+    pub sequential: bool,
+}
+
 /// A shutdown handle.
 ///
 /// # Children
 /// "Children" can be created by invoking the [`Self::child`] method.
 /// A children is another shutdown handle that would automatically be shut down if the parent is shut down.
 ///
+/// Children are referenced weakly by their parents,
+/// a child is removed from its parents once all its handles, waiters and tasks are dropped.
+/// It can also be removed explicitly by [`Self::detach`].
+///
 /// ## Circular Reference
-/// The bahaviour is undefined if circular reference of children occurs.
+/// Circular reference of children is rejected by [`Self::adopt`] with a [`CycleError`].
 ///
-/// # One-time Usage
-/// This is designed for one-time usage for managing shutdown signals.
-/// The bahaviour is undefined if [`Self::shut`] is called for multiple times.
+/// # Phases
+/// [`Self::shut`] starts a graceful shutdown, which can be escalated by [`Self::force`].
+/// See [`Phase`].
+///
+/// # Generations
+/// Calling [`Self::shut`] for multiple times has no effect after the first call.
+/// A handle that is shut down can be restarted by [`Self::reset`],
+/// which starts a new generation of it while keeping its place in the tree.
 #[derive(Clone)]
 pub struct ShutUp(Arc<ShutUpInner>);
 
 struct ShutUpInner {
-    signal: broadcast::Sender<()>,
-    children: Mutex<Vec<ShutUp>>,
-    hooks: Mutex<Vec<Box<dyn FnOnce() + Send>>>,
-    status: AtomicBool,
+    name: Option<String>,
+    children: Mutex<Vec<Weak<ShutUpInner>>>,
+    parents: Mutex<Vec<Weak<ShutUpInner>>>,
+    teardown: Mutex<Teardown>,
+    pre_stop: Mutex<Duration>,
+    /// Current generation, replaced by [`ShutUp::reset`].
+    generation: Mutex<Arc<Generation>>,
+}
+
+/// An asynchronous hook with its name, if any.
+type AsyncHook = (Option<String>, Pin<Box<dyn Wait>>);
+
+/// State of a handle that is renewed on every [`ShutUp::reset`].
+struct Generation {
+    id: u64,
+    signal: Latch,
+    reason: OnceLock<Reason>,
+    forced: Latch,
+    hooks: Mutex<Vec<Hook>>,
+    async_hooks: Mutex<Vec<AsyncHook>>,
+    /// Names of asynchronous hooks being run by [`ShutUp::shutdown`].
+    running: Mutex<Vec<Option<String>>>,
+    /// Whether async hooks have been claimed by a [`ShutUp::shutdown`] call.
+    claimed: AtomicBool,
+    /// Set when async hooks of this handle and its children have completed.
+    completed: Latch,
+    /// Set when shutdown begins, possibly before the pre-stop delay elapses.
+    pre: Latch,
+    /// Whether a delayed shutdown is in progress.
+    delaying: AtomicBool,
+    /// Whether children and synchronous hooks are left to [`ShutUp::shutdown`],
+    /// see [`Teardown::sequential`].
+    staged: AtomicBool,
+    /// Report of a shutdown signaled after the pre-stop delay.
+    delayed: Mutex<Option<ShutdownReport>>,
+    /// Set when the shutdown signal has been fully processed after the pre-stop delay.
+    elapsed: Latch,
+    /// Number of alive tasks tracked by [`ShutUp::track`].
+    tasks: Counter,
+    /// Furthest [`State`] observed by [`ShutUp::state`].
+    state: AtomicU8,
+    /// Set when this generation is replaced by [`ShutUp::reset`].
+    retired: Latch,
+}
+
+impl Generation {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            signal: Latch::new(),
+            reason: OnceLock::new(),
+            forced: Latch::new(),
+            hooks: Mutex::new(vec![]),
+            async_hooks: Mutex::new(vec![]),
+            running: Mutex::new(vec![]),
+            claimed: AtomicBool::new(false),
+            completed: Latch::new(),
+            pre: Latch::new(),
+            delaying: AtomicBool::new(false),
+            staged: AtomicBool::new(false),
+            delayed: Mutex::new(None),
+            elapsed: Latch::new(),
+            tasks: Counter::default(),
+            state: AtomicU8::new(State::Running as u8),
+            retired: Latch::new(),
+        }
+    }
 }
 
 impl ShutUp {
-    pub(crate) fn root() -> Self {
+    pub(crate) fn root(name: Option<String>) -> Self {
         Self(Arc::new(ShutUpInner {
-            signal: broadcast::channel(1).0,
+            name,
             children: Mutex::new(vec![]),
-            hooks: Mutex::new(vec![]),
-            status: AtomicBool::new(false),
+            parents: Mutex::new(vec![]),
+            teardown: Mutex::new(Teardown::default()),
+            pre_stop: Mutex::new(Duration::ZERO),
+            generation: Mutex::new(Arc::new(Generation::new(0))),
         }))
     }
 
+    /// Get the current generation of this handle.
+    fn current(&self) -> Arc<Generation> {
+        lock(&self.0.generation).clone()
+    }
+
+    /// Get the generation number of this handle, starting from `0` and increased by [`Self::reset`].
+    pub fn generation(&self) -> u64 {
+        self.current().id
+    }
+
+    /// Restart this handle after it is shut down, returning `false` if it is not shut down yet.
+    ///
+    /// A new generation is started with no hooks and no tracked tasks,
+    /// while the name, teardown strategy, pre-stop delay, parents and children are kept.
+    /// Waiters created before the reset still observe the previous generation,
+    /// so they resolve as usual, while those created afterwards wait for the next shutdown.
+    /// Pending asynchronous hooks of the previous generation are dropped.
+    ///
+    /// Children are not reset along with this handle.
+    /// The handle is shut down again immediately if any of its parents is still shut down.
+    pub fn reset(&self) -> bool {
+        let old = {
+            let mut generation = lock(&self.0.generation);
+            if !generation.signal.is_set() {
+                return false;
+            }
+            let new = Arc::new(Generation::new(generation.id + 1));
+            mem::replace(&mut *generation, new)
+        };
+        old.retired.set();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            handle = self.display_name(),
+            generation = self.generation(),
+            "handle reset"
+        );
+        let parents = lock(&self.0.parents).clone();
+        for i in parents.iter().filter_map(Weak::upgrade) {
+            Self(i).inherit(self);
+        }
+        true
+    }
+
     /// Create a child of this shutdown handle.
+    ///
+    /// The child is shut down immediately if this handle is already shut down.
     pub fn child(&self) -> Self {
-        let new = Self::root();
-        self.0.children.lock().unwrap().push(new.clone());
+        let new = Self::root(None);
+        self.link(&new);
+        new
+    }
+
+    /// Create a named child of this shutdown handle.
+    ///
+    /// The child is shut down immediately if this handle is already shut down.
+    pub fn child_named(&self, name: impl Into<String>) -> Self {
+        let new = Self::root(Some(name.into()));
+        self.link(&new);
         new
     }
 
     /// Adopt another shutdown handle as child.
-    pub fn adopt(&self, child: &Self) {
-        self.0.children.lock().unwrap().push(child.clone());
+    ///
+    /// The child is shut down immediately if this handle is already shut down.
+    /// Adopting a handle that is already a child of this handle has no effect.
+    ///
+    /// # Errors
+    /// Returns [`CycleError`] if the child is this handle or one of its ancestors.
+    pub fn adopt(&self, child: &Self) -> Result<(), CycleError> {
+        // serialize adoptions so that concurrent calls cannot form a cycle together
+        static ADOPT: Mutex<()> = Mutex::new(());
+        let adopt = lock(&ADOPT);
+        if self.is_descendant_of(child) {
+            return Err(CycleError);
+        }
+        let adopted = lock(&self.0.children)
+            .iter()
+            .any(|i| i.as_ptr() == Arc::as_ptr(&child.0));
+        if adopted {
+            return Ok(());
+        }
+        self.attach(child);
+        // shutting the child down runs its hooks, which may adopt as well
+        drop(adopt);
+        self.inherit(child);
+        Ok(())
+    }
+
+    /// Check whether this handle is `other` or one of its descendants.
+    fn is_descendant_of(&self, other: &Self) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![self.0.clone()];
+        while let Some(i) = stack.pop() {
+            if Arc::ptr_eq(&i, &other.0) {
+                return true;
+            }
+            if !visited.insert(Arc::as_ptr(&i)) {
+                continue;
+            }
+            let parents = lock(&i.parents);
+            stack.extend(parents.iter().filter_map(Weak::upgrade));
+        }
+        false
+    }
+
+    fn link(&self, child: &Self) {
+        self.attach(child);
+        self.inherit(child);
+    }
+
+    fn attach(&self, child: &Self) {
+        lock(&self.0.children).push(Arc::downgrade(&child.0));
+        lock(&child.0.parents).push(Arc::downgrade(&self.0));
+    }
+
+    /// Bring a child to the phase of this handle.
+    ///
+    /// Children attached after `shut` took its snapshot would otherwise be missed.
+    fn inherit(&self, child: &Self) {
+        if self.current().pre.is_set() {
+            child.pre_signal();
+        }
+        if let Some(reason) = self.reason() {
+            child.signal(reason, false);
+        }
+        if self.phase() == Phase::Forced {
+            child.force();
+        }
+    }
+
+    /// Remove this handle from all of its parents.
+    ///
+    /// The handle would no longer be shut down by its former parents.
+    pub fn detach(&self) {
+        let parents = mem::take(&mut *lock(&self.0.parents));
+        for i in parents.iter().filter_map(Weak::upgrade) {
+            i.remove_child(Arc::as_ptr(&self.0));
+        }
+    }
+
+    /// Get the alive children of this handle.
+    pub(crate) fn children(&self) -> Vec<Self> {
+        let children = lock(&self.0.children);
+        children
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(Self)
+            .collect()
+    }
+
+    /// Get the alive children of this handle in teardown order.
+    fn teardown_order(&self) -> Vec<Self> {
+        let mut children = self.children();
+        if !self.teardown().fifo {
+            children.reverse();
+        }
+        children
+    }
+
+    /// Get the strategy of tearing down children of this handle.
+    pub fn teardown(&self) -> Teardown {
+        *lock(&self.0.teardown)
+    }
+
+    /// Set the strategy of tearing down children of this handle.
+    pub fn set_teardown(&self, teardown: Teardown) {
+        *lock(&self.0.teardown) = teardown;
     }
 
     /// Create a new shutdown handle.
@@ -58,11 +374,88 @@ impl ShutUp {
         ROOT.child()
     }
 
+    /// Create a new named shutdown handle.
+    ///
+    /// Names are used for identifying handles in [`Self::tree`], and need not be unique.
+    pub fn named(name: impl Into<String>) -> Self {
+        ROOT.child_named(name)
+    }
+
+    /// Get the name of this handle.
+    pub fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+
+    /// Get the name of this handle for display.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn display_name(&self) -> &str {
+        self.name().unwrap_or("<anonymous>")
+    }
+
     /// Wait until a shutdown signal is received.
-    pub fn wait(&self) -> impl Wait {
-        let mut signal = self.0.signal.subscribe();
+    ///
+    /// The returned future resolves immediately if this handle is already shut down.
+    pub fn wait(&self) -> impl Wait + use<> {
+        self.wait_for(Phase::Graceful)
+    }
+
+    /// Block the current thread until a shutdown signal is received.
+    ///
+    /// Does not require an async runtime, but must not be called within one.
+    pub fn wait_blocking(&self) {
+        block_on(self.wait(), None);
+    }
+
+    /// Block the current thread until a shutdown signal is received or the timeout elapses.
+    ///
+    /// Returns whether this handle is shut down.
+    /// Does not require an async runtime, but must not be called within one.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        block_on(self.wait(), Some(Instant::now() + timeout)).is_some()
+    }
+
+    /// Wait until a shutdown signal is received, returning the [`Reason`] of the shutdown.
+    pub fn wait_reason(&self) -> impl Future<Output = Reason> + Send + use<> {
+        let generation = self.current();
+        let wait = self.wait_in(&generation, Phase::Graceful);
         async move {
-            let _ = signal.recv().await;
+            wait.await;
+            generation.reason.get().cloned().unwrap_or_default()
+        }
+    }
+
+    /// Wait until shutdown of this handle begins.
+    ///
+    /// Resolves immediately when shutdown is triggered on this handle or its ancestors,
+    /// without waiting for the pre-stop delay, see [`Self::set_pre_stop`].
+    pub fn wait_pre(&self) -> impl Wait + use<> {
+        self.wait_for(Phase::PreStop)
+    }
+
+    /// Wait until this handle reaches the given phase.
+    ///
+    /// The returned future resolves immediately if this handle has already reached the phase.
+    pub fn wait_for(&self, phase: Phase) -> impl Wait + use<> {
+        self.wait_in(&self.current(), phase)
+    }
+
+    /// Wait until the given generation of this handle reaches the phase.
+    fn wait_in(&self, generation: &Generation, phase: Phase) -> impl Wait + use<> {
+        let wait = match phase {
+            Phase::Running => None,
+            Phase::PreStop => Some(generation.pre.wait()),
+            Phase::Graceful => Some(generation.signal.wait()),
+            Phase::Forced => Some(generation.forced.wait()),
+        };
+        // keep this handle reachable from its parents while waiting
+        let this = self.clone();
+        async move {
+            if let Some(wait) = wait {
+                wait.await;
+            }
+            #[cfg(feature = "tracing")]
+            tracing::trace!(handle = this.display_name(), ?phase, "wait resolved");
+            drop(this);
         }
     }
 
@@ -70,32 +463,747 @@ impl ShutUp {
     ///
     /// Used for polling shutdown status instead of wait asynchronously for shutdown.
     pub fn off(&self) -> bool {
-        self.0.status.load(Ordering::Relaxed)
+        self.current().signal.is_set()
+    }
+
+    /// Get the reason of shutdown, or `None` if this handle is not shut down.
+    pub fn reason(&self) -> Option<Reason> {
+        let generation = self.current();
+        if !generation.signal.is_set() {
+            return None;
+        }
+        generation.reason.get().cloned()
+    }
+
+    /// Get the current phase of this handle.
+    pub fn phase(&self) -> Phase {
+        let generation = self.current();
+        if generation.forced.is_set() {
+            Phase::Forced
+        } else if generation.signal.is_set() {
+            Phase::Graceful
+        } else if generation.pre.is_set() {
+            Phase::PreStop
+        } else {
+            Phase::Running
+        }
     }
 
     /// Register a hook to be run when this handle is shut down.
+    ///
+    /// Same as [`Self::register`] with an anonymous [`Hook`] of priority `0`.
+    ///
+    /// Hooks are dropped without running if all clones of this handle are dropped before shutdown,
+    /// as the handle is then removed from its parents.
     pub fn register_hook(&self, hook: impl FnOnce() + Send + 'static) {
-        let hook = Box::new(hook);
-        self.0.hooks.lock().unwrap().push(hook);
+        self.register(Hook::new(hook));
+    }
+
+    /// Register a hook with ordering constraints to be run when this handle is shut down.
+    ///
+    /// Hooks are run after children are shut down, see [`Hook`] for the order among hooks.
+    /// See [`Self::register_hook`] for hooks of dropped handles.
+    pub fn register(&self, hook: Hook) {
+        lock(&self.current().hooks).push(hook);
+    }
+
+    /// Register an asynchronous hook to be run when this handle is shut down.
+    ///
+    /// Asynchronous hooks are only run by [`Self::shutdown`],
+    /// they are never polled if this handle is shut down by [`Self::shut`] alone.
+    ///
+    /// Like synchronous hooks, they never run if all clones of this handle are dropped before shutdown.
+    pub fn register_async_hook(&self, hook: impl Wait) {
+        let hook = Box::pin(hook);
+        lock(&self.current().async_hooks).push((None, hook));
+    }
+
+    /// Register a named asynchronous hook, identified by the name in [`ShutdownReport`].
+    ///
+    /// See [`Self::register_async_hook`].
+    pub fn register_async_hook_named(&self, name: impl Into<String>, hook: impl Wait) {
+        let hook = Box::pin(hook);
+        lock(&self.current().async_hooks).push((Some(name.into()), hook));
     }
 
     /// Triggers shutdown on the current handle and its children.
-    pub fn shut(&self) {
-        if self.off() {
+    ///
+    /// Same as [`Self::shut_with`] with [`Reason::Unspecified`].
+    pub fn shut(&self) -> ShutdownReport {
+        self.shut_with(Reason::Unspecified)
+    }
+
+    /// Triggers shutdown on the current handle and its children with the given reason.
+    ///
+    /// Children inherit the reason of their parent.
+    /// The reason is ignored if this handle is already shut down.
+    ///
+    /// A panicking hook does not prevent other hooks from running,
+    /// panics are caught and collected into the returned report instead.
+    /// The report is empty if this handle is already shut down,
+    /// or if the shutdown is delayed by [`Self::set_pre_stop`].
+    pub fn shut_with(&self, reason: impl Into<Reason>) -> ShutdownReport {
+        let start = Instant::now();
+        let mut report = self.trigger(&self.current(), reason.into(), false);
+        report.tasks = self.alive_tasks();
+        report.duration = start.elapsed();
+        report
+    }
+
+    /// Get the pre-stop delay of this handle.
+    pub fn pre_stop(&self) -> Duration {
+        *lock(&self.0.pre_stop)
+    }
+
+    /// Set the pre-stop delay of this handle, which is zero by default.
+    ///
+    /// When shutdown is triggered on this handle, it and its descendants turn unready immediately,
+    /// see [`Self::readiness`], while the shutdown signal, propagation to children and hooks
+    /// are delayed on a background thread, giving load balancers time to stop sending traffic.
+    /// The delay does not apply when this handle is shut down by its parents,
+    /// and is skipped by [`Self::force`], which wakes the background thread early.
+    ///
+    /// Synchronous hooks of a delayed shutdown run on that background thread,
+    /// outside of any async runtime context, so they must not rely on one,
+    /// e.g. by calling `tokio::spawn`. Asynchronous hooks are still run by [`Self::shutdown`].
+    pub fn set_pre_stop(&self, delay: Duration) {
+        *lock(&self.0.pre_stop) = delay;
+    }
+
+    /// Shut down the given generation of this handle, honoring the pre-stop delay.
+    ///
+    /// If `staged`, sequential teardown is left to [`Self::complete`].
+    fn trigger(
+        &self,
+        generation: &Arc<Generation>,
+        reason: Reason,
+        staged: bool,
+    ) -> ShutdownReport {
+        let delay = self.pre_stop();
+        if delay.is_zero() {
+            return self.signal_in(generation, reason, staged);
+        }
+        let _ = generation.reason.set(reason.clone());
+        self.pre_signal_in(generation);
+        if generation.signal.is_set() || generation.delaying.swap(true, Ordering::AcqRel) {
+            return ShutdownReport::default();
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            handle = self.display_name(),
+            ?delay,
+            "shutdown begun, waiting for pre-stop delay"
+        );
+        let this = self.clone();
+        let generation = generation.clone();
+        thread::spawn(move || {
+            // `force` signals the handle itself, so there is nothing left to wait for
+            let _ = block_on(generation.forced.wait(), Some(Instant::now() + delay));
+            let report = this.signal_inner(&generation, reason, staged);
+            lock(&generation.delayed)
+                .get_or_insert_default()
+                .merge(report);
+            generation.elapsed.set();
+        });
+        ShutdownReport::default()
+    }
+
+    /// Mark this handle and its descendants as beginning shutdown.
+    fn pre_signal(&self) {
+        self.pre_signal_in(&self.current());
+    }
+
+    fn pre_signal_in(&self, generation: &Generation) {
+        if !generation.pre.set() {
             return;
         }
-        let _ = self.0.signal.send(());
-        self.0.status.store(true, Ordering::Relaxed);
-        for i in self.0.children.lock().unwrap().drain(..) {
-            i.shut();
+        for i in self.children() {
+            i.pre_signal();
+        }
+    }
+
+    /// Shut down this handle and its children immediately, running synchronous hooks.
+    ///
+    /// If `staged`, handles with sequential teardown leave their children and synchronous hooks
+    /// to [`Self::complete`].
+    fn signal(&self, reason: Reason, staged: bool) -> ShutdownReport {
+        self.signal_in(&self.current(), reason, staged)
+    }
+
+    fn signal_in(&self, generation: &Generation, reason: Reason, staged: bool) -> ShutdownReport {
+        let report = self.signal_inner(generation, reason, staged);
+        generation.elapsed.set();
+        report
+    }
+
+    fn signal_inner(
+        &self,
+        generation: &Generation,
+        reason: Reason,
+        staged: bool,
+    ) -> ShutdownReport {
+        let mut report = ShutdownReport::default();
+        // the reason must be visible once the signal is observed
+        let _ = generation.reason.set(reason);
+        generation.pre.set();
+        if !generation.signal.set() {
+            return report;
+        }
+        report.handles.push(self.name().map(String::from));
+        let reason = generation.reason.get().unwrap();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("shut", handle = self.display_name(), %reason).entered();
+        #[cfg(feature = "tracing")]
+        tracing::debug!("shutdown signaled");
+        if staged && self.teardown().sequential {
+            generation.staged.store(true, Ordering::Release);
+            return report;
+        }
+        report.merge(self.tear_down(generation, reason, staged));
+        report
+    }
+
+    /// Shut down the children of this handle and run the synchronous hooks of the given generation.
+    fn tear_down(&self, generation: &Generation, reason: &Reason, staged: bool) -> ShutdownReport {
+        let mut report = ShutdownReport::default();
+        let children = self.teardown_order();
+        for i in children {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(child = i.display_name(), "propagating shutdown to child");
+            report.merge(i.signal(reason.clone(), staged));
+        }
+        report.merge(self.run_hooks(generation));
+        report
+    }
+
+    /// Run the synchronous hooks of the given generation of this handle.
+    fn run_hooks(&self, generation: &Generation) -> ShutdownReport {
+        let mut report = ShutdownReport::default();
+        // no lock is held while running hooks, so they may re-enter this handle
+        let hooks = mem::take(&mut *lock(&generation.hooks));
+        for i in hook::sort(hooks) {
+            let hook = i.name().map(String::from);
+            let start = Instant::now();
+            let result = i.run();
+            let duration = start.elapsed();
+            #[cfg(feature = "tracing")]
+            match &result {
+                Ok(()) => tracing::debug!(hook, ?duration, "hook completed"),
+                Err(err) => tracing::warn!(hook, ?duration, panic = err.message, "hook panicked"),
+            }
+            if let Err(err) = result {
+                report.panics.push(err);
+            }
+            report.hooks.push(HookRun {
+                handle: self.name().map(String::from),
+                hook,
+                asynchronous: false,
+                duration,
+            });
+        }
+        report
+    }
+
+    /// Escalates shutdown of the current handle and its children to [`Phase::Forced`].
+    ///
+    /// Asynchronous hooks still running are aborted.
+    pub fn force(&self) {
+        let generation = self.current();
+        self.signal_in(&generation, Reason::Unspecified, false);
+        if !generation.forced.set() {
+            return;
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(handle = self.display_name(), "shutdown forced");
+        if generation.staged.load(Ordering::Acquire) {
+            // children not reached by sequential teardown yet still inherit the reason
+            let reason = generation.reason.get().unwrap();
+            let report = self.tear_down(&generation, reason, false);
+            lock(&generation.delayed)
+                .get_or_insert_default()
+                .merge(report);
+        }
+        let children = self.teardown_order();
+        for i in children {
+            i.force();
+        }
+    }
+
+    /// Triggers shutdown on the current handle and its children,
+    /// then run asynchronous hooks registered on them.
+    ///
+    /// The returned future resolves when all the asynchronous hooks have completed,
+    /// or have been aborted by [`Self::force`].
+    /// Asynchronous hooks of children are run before those of the current handle,
+    /// see [`Teardown`] for the order among children.
+    ///
+    /// The returned report only covers asynchronous hooks run by this call,
+    /// hooks already claimed by another call are waited for but not reported.
+    ///
+    /// The pre-stop delay is honored, see [`Self::set_pre_stop`].
+    pub fn shutdown(&self) -> impl Future<Output = ShutdownReport> + Send + use<> {
+        let start = Instant::now();
+        let generation = self.current();
+        let report = self.trigger(&generation, Reason::Unspecified, true);
+        let elapsed = race(generation.elapsed.wait(), generation.forced.wait());
+        let this = self.clone();
+        async move {
+            elapsed.await;
+            let mut report = report;
+            if let Some(delayed) = lock(&generation.delayed).take() {
+                report.merge(delayed);
+            }
+            let report = Arc::new(Mutex::new(report));
+            this.complete(&generation, &report).await;
+            let mut report = mem::take(&mut *lock(&report));
+            report.tasks = this.alive_tasks();
+            report.duration = start.elapsed();
+            report
+        }
+    }
+
+    /// Same as [`Self::shutdown`], but escalates to [`Phase::Forced`]
+    /// if the asynchronous hooks do not complete within `timeout`.
+    ///
+    /// The timeout is counted after the pre-stop delay.
+    /// Must be called within a Tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn shutdown_timeout(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = ShutdownReport> + Send + use<> {
+        let shutdown = self.shutdown();
+        let this = self.clone();
+        async move {
+            let mut shutdown = std::pin::pin!(shutdown);
+            let timeout = timeout + this.pre_stop();
+            match tokio::time::timeout(timeout, shutdown.as_mut()).await {
+                Ok(report) => report,
+                Err(_) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        handle = this.display_name(),
+                        ?timeout,
+                        "shutdown timed out, forcing"
+                    );
+                    this.force();
+                    // let the aborted hooks be marked as completed
+                    let mut report = shutdown.await;
+                    report.timed_out = true;
+                    report
+                }
+            }
+        }
+    }
+
+    /// Run asynchronous hooks of the given generation of the current handle and its children,
+    /// or wait for them if they have been claimed by another call.
+    fn complete(
+        &self,
+        generation: &Arc<Generation>,
+        report: &Arc<Mutex<ShutdownReport>>,
+    ) -> Pin<Box<dyn Wait>> {
+        let forced = generation.forced.wait();
+        if generation.claimed.swap(true, Ordering::AcqRel) {
+            return Box::pin(race(generation.completed.wait(), forced));
+        }
+        let sequential = self.teardown().sequential;
+        let staged = generation.staged.load(Ordering::Acquire);
+        // staged children are not signaled yet, so their hooks are claimed one by one
+        let children = if staged {
+            vec![]
+        } else {
+            let children = self.teardown_order();
+            children
+                .iter()
+                .map(|i| i.complete(&i.current(), report))
+                .collect::<Vec<_>>()
+        };
+        let hooks = mem::take(&mut *lock(&generation.async_hooks));
+        // hooks are counted as aborted until they complete
+        lock(report).aborted += hooks.len();
+        let hooks = hooks
+            .into_iter()
+            .map(|(name, hook)| {
+                let handle = self.name().map(String::from);
+                let generation = generation.clone();
+                Box::pin(run_async_hook(
+                    generation,
+                    handle,
+                    name,
+                    hook,
+                    report.clone(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let this = self.clone();
+        let generation = generation.clone();
+        let report = report.clone();
+        let complete = async move {
+            let run = async {
+                if staged {
+                    let reason = generation.reason.get().unwrap();
+                    for i in this.teardown_order() {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(child = i.display_name(), "propagating shutdown to child");
+                        let signaled = i.signal(reason.clone(), true);
+                        lock(&report).merge(signaled);
+                        i.complete(&i.current(), &report).await;
+                    }
+                    let ran = this.run_hooks(&generation);
+                    lock(&report).merge(ran);
+                } else if sequential {
+                    for i in children {
+                        i.await;
+                    }
+                } else {
+                    join_all(children).await;
+                }
+                join_all(hooks).await;
+            };
+            race(run, forced).await;
+            // teardown interrupted by `force` is reported here
+            if let Some(delayed) = lock(&generation.delayed).take() {
+                lock(&report).merge(delayed);
+            }
+            generation.completed.set();
+            // keep this handle reachable from its parents while running
+            drop(this);
+        };
+        #[cfg(feature = "tracing")]
+        let complete = tracing::Instrument::instrument(
+            complete,
+            tracing::debug_span!("complete", handle = self.display_name()),
+        );
+        Box::pin(complete)
+    }
+}
+
+impl ShutUpInner {
+    fn remove_child(&self, child: *const ShutUpInner) {
+        let mut children = lock(&self.children);
+        children.retain(|i| i.as_ptr() != child);
+    }
+
+    fn remove_parent(&self, parent: *const ShutUpInner) {
+        let mut parents = lock(&self.parents);
+        parents.retain(|i| i.as_ptr() != parent);
+    }
+}
+
+impl Drop for ShutUpInner {
+    fn drop(&mut self) {
+        let parents = mem::take(
+            self.parents
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        for i in parents.iter().filter_map(Weak::upgrade) {
+            i.remove_child(self);
+        }
+        // dead back-links would otherwise pin this allocation and pile up in shared children
+        let children = mem::take(
+            self.children
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        for i in children.iter().filter_map(Weak::upgrade) {
+            i.remove_parent(self);
         }
-        for i in self.0.hooks.lock().unwrap().drain(..) {
-            i();
+    }
+}
+
+impl fmt::Debug for ShutUp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutUp")
+            .field("name", &self.name())
+            .field("generation", &self.generation())
+            .field("phase", &self.phase())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ShutUp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Entry of an asynchronous hook being run, removed once the hook completes or is aborted.
+struct RunningHook {
+    generation: Arc<Generation>,
+    hook: Option<String>,
+}
+
+impl RunningHook {
+    fn new(generation: Arc<Generation>, hook: Option<String>) -> Self {
+        lock(&generation.running).push(hook.clone());
+        Self { generation, hook }
+    }
+}
+
+impl Drop for RunningHook {
+    fn drop(&mut self) {
+        let mut running = lock(&self.generation.running);
+        // entries of the same name are interchangeable
+        if let Some(i) = running.iter().position(|i| *i == self.hook) {
+            running.remove(i);
         }
     }
 }
 
+/// Run an asynchronous hook of the given generation, recording it into the report.
+async fn run_async_hook(
+    generation: Arc<Generation>,
+    handle: Option<String>,
+    hook: Option<String>,
+    run: Pin<Box<dyn Wait>>,
+    report: Arc<Mutex<ShutdownReport>>,
+) {
+    let running = RunningHook::new(generation, hook.clone());
+    let start = Instant::now();
+    let result = catch_unwind(run)
+        .await
+        .map_err(|x| HookPanic::new(hook.clone(), x));
+    let duration = start.elapsed();
+    drop(running);
+    #[cfg(feature = "tracing")]
+    match &result {
+        Ok(()) => tracing::debug!(hook, ?duration, "async hook completed"),
+        Err(err) => tracing::warn!(hook, ?duration, panic = err.message, "async hook panicked"),
+    }
+    let mut report = lock(&report);
+    report.aborted -= 1;
+    if let Err(err) = result {
+        report.panics.push(err);
+    }
+    report.hooks.push(HookRun {
+        handle,
+        hook,
+        asynchronous: true,
+        duration,
+    });
+}
+
 /// Root shutdown handle of the current process.
 ///
 /// All handles created by [`ShutUp::new`] would be children of this handle.
-pub static ROOT: LazyLock<ShutUp> = LazyLock::new(|| ShutUp::root());
+pub static ROOT: LazyLock<ShutUp> = LazyLock::new(|| ShutUp::root(Some("root".into())));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pre_stop_delays_shutdown() {
+        let handle = ShutUp::new();
+        let child = handle.child();
+        handle.set_pre_stop(Duration::from_millis(50));
+        let ready = handle.readiness();
+        let report = handle.shut_with(Reason::Code(3));
+        assert!(report.handles.is_empty());
+        assert_eq!(handle.phase(), Phase::PreStop);
+        assert_eq!(child.phase(), Phase::PreStop);
+        assert!(!ready.is_ready());
+        assert!(!handle.off());
+        assert!(block_on(handle.wait_pre(), None).is_some());
+        assert!(child.wait_timeout(Duration::from_secs(5)));
+        assert!(handle.off());
+        assert!(matches!(handle.reason(), Some(Reason::Code(3))));
+    }
+
+    #[test]
+    fn force_skips_pre_stop() {
+        let handle = ShutUp::new();
+        let child = handle.child();
+        handle.set_pre_stop(Duration::from_secs(60));
+        handle.shut();
+        assert_eq!(handle.phase(), Phase::PreStop);
+        handle.force();
+        assert_eq!(handle.phase(), Phase::Forced);
+        assert_eq!(child.phase(), Phase::Forced);
+        assert!(handle.wait_timeout(Duration::ZERO));
+    }
+
+    #[test]
+    fn force_wakes_pre_stop_thread() {
+        let handle = ShutUp::new();
+        handle.set_pre_stop(Duration::from_secs(60));
+        handle.shut();
+        handle.force();
+        // the background thread holds a clone of the handle until it wakes up
+        let inner = Arc::downgrade(&handle.0);
+        drop(handle);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while inner.strong_count() > 0 {
+            assert!(Instant::now() < deadline);
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn pre_stop_applies_to_direct_shutdown_only() {
+        let parent = ShutUp::new();
+        let child = parent.child();
+        child.set_pre_stop(Duration::from_secs(60));
+        parent.shut();
+        assert_eq!(child.phase(), Phase::Graceful);
+    }
+
+    #[test]
+    fn child_shut_during_parent_pre_stop() {
+        let parent = ShutUp::new();
+        let child = parent.child();
+        parent.set_pre_stop(Duration::from_secs(60));
+        parent.shut();
+        assert_eq!(child.phase(), Phase::PreStop);
+        // the child is not delayed, so shutting it directly takes effect immediately
+        child.shut();
+        assert_eq!(child.phase(), Phase::Graceful);
+        assert_eq!(parent.phase(), Phase::PreStop);
+        parent.force();
+    }
+
+    #[test]
+    fn sequential_shutdown_signals_children_in_turn() {
+        let parent = ShutUp::new();
+        parent.set_teardown(Teardown {
+            fifo: true,
+            sequential: true,
+        });
+        let first = parent.child();
+        let second = parent.child();
+        let log = Arc::new(Mutex::new(vec![]));
+        let (log1, peer) = (log.clone(), second.clone());
+        first.register_async_hook(async move {
+            lock(&log1).push(("first", peer.off()));
+        });
+        let (log2, peer) = (log.clone(), first.clone());
+        second.register_async_hook(async move {
+            lock(&log2).push(("second", peer.off()));
+        });
+        let (log3, peer) = (log.clone(), second.clone());
+        parent.register_hook(move || lock(&log3).push(("parent", peer.off())));
+        let report = block_on(parent.shutdown(), None).unwrap();
+        assert_eq!(
+            *lock(&log),
+            [("first", false), ("second", true), ("parent", true)]
+        );
+        assert_eq!(report.handles.len(), 3);
+        assert_eq!(report.hooks.len(), 3);
+    }
+
+    #[test]
+    fn sequential_shut_signals_children_immediately() {
+        let parent = ShutUp::new();
+        parent.set_teardown(Teardown {
+            fifo: false,
+            sequential: true,
+        });
+        let first = parent.child();
+        let second = parent.child();
+        parent.shut();
+        assert!(first.off());
+        assert!(second.off());
+    }
+
+    #[test]
+    fn force_during_sequential_shutdown() {
+        let parent = ShutUp::new();
+        parent.set_teardown(Teardown {
+            fifo: true,
+            sequential: true,
+        });
+        let first = parent.child();
+        let second = parent.child();
+        first.register_async_hook(std::future::pending());
+        let shutdown = parent.shutdown();
+        assert!(parent.off());
+        assert!(!second.off());
+        parent.force();
+        assert_eq!(second.phase(), Phase::Forced);
+        let report = block_on(shutdown, None).unwrap();
+        assert_eq!(report.aborted, 1);
+        assert_eq!(report.handles.len(), 3);
+    }
+
+    #[test]
+    fn adopt_rejects_cycles() {
+        let parent = ShutUp::new();
+        let child = parent.child();
+        let grandchild = child.child();
+        assert_eq!(parent.adopt(&parent), Err(CycleError));
+        assert_eq!(child.adopt(&parent), Err(CycleError));
+        assert_eq!(grandchild.adopt(&parent), Err(CycleError));
+        assert_eq!(lock(&parent.0.parents).len(), 1);
+    }
+
+    #[test]
+    fn adopt_existing_child() {
+        let parent = ShutUp::new();
+        let child = parent.child();
+        assert_eq!(parent.adopt(&child), Ok(()));
+        assert_eq!(parent.children().len(), 1);
+        assert_eq!(lock(&child.0.parents).len(), 1);
+        let report = parent.shut();
+        assert_eq!(report.handles.len(), 2);
+    }
+
+    #[test]
+    fn reset_while_running() {
+        let handle = ShutUp::new();
+        assert!(!handle.reset());
+        assert_eq!(handle.generation(), 0);
+    }
+
+    #[test]
+    fn old_waiter_resolves_after_reset() {
+        let handle = ShutUp::new();
+        let wait = handle.wait();
+        handle.shut();
+        assert!(handle.reset());
+        assert_eq!(handle.generation(), 1);
+        assert!(!handle.off());
+        assert!(block_on(wait, Some(Instant::now())).is_some());
+    }
+
+    #[test]
+    fn new_waiter_resolved_by_parent() {
+        let parent = ShutUp::new();
+        let child = parent.child();
+        parent.shut();
+        assert!(parent.reset());
+        assert!(child.reset());
+        let mut wait = Box::pin(child.wait());
+        assert!(block_on(wait.as_mut(), Some(Instant::now())).is_none());
+        parent.shut();
+        assert!(block_on(wait, Some(Instant::now())).is_some());
+        assert!(child.off());
+    }
+
+    #[test]
+    fn reset_under_shut_parent() {
+        let parent = ShutUp::new();
+        let child = parent.child();
+        parent.shut_with(Reason::Code(2));
+        assert!(child.reset());
+        assert_eq!(child.generation(), 1);
+        assert!(child.off());
+        assert!(matches!(child.reason(), Some(Reason::Code(2))));
+    }
+
+    #[test]
+    fn detach_from_every_parent() {
+        let first = ShutUp::new();
+        let second = ShutUp::new();
+        let child = first.child();
+        second.adopt(&child).unwrap();
+        child.detach();
+        assert!(first.children().is_empty());
+        assert!(second.children().is_empty());
+        assert!(lock(&child.0.parents).is_empty());
+        first.shut();
+        second.shut();
+        assert!(!child.off());
+    }
+}
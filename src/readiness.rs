@@ -0,0 +1,40 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use crate::{Phase, ShutUp};
+
+/// Readiness of a service bound to a shutdown handle, e.g. for a readiness probe.
+///
+/// Turns unready as soon as the handle reaches [`Phase::PreStop`],
+/// before the pre-stop delay elapses and children are shut down, see [`ShutUp::set_pre_stop`].
+#[derive(Clone)]
+pub struct Readiness {
+    handle: ShutUp,
+    ready: Arc<AtomicBool>,
+}
+
+impl Readiness {
+    /// Check whether the service is ready.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire) && self.handle.phase() == Phase::Running
+    }
+
+    /// Mark the service as ready or not, e.g. while warming up.
+    ///
+    /// A service is never ready after shutdown has begun.
+    pub fn set_ready(&self, ready: bool) {
+        self.ready.store(ready, Ordering::Release);
+    }
+}
+
+impl ShutUp {
+    /// Create a readiness bound to this handle, initially ready.
+    pub fn readiness(&self) -> Readiness {
+        Readiness {
+            handle: self.clone(),
+            ready: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::{error::Error, fmt, sync::Arc};
+
+/// Reason of a shutdown.
+///
+/// Given by [`crate::ShutUp::shut_with`] and inherited by children.
+#[derive(Clone, Debug, Default)]
+pub enum Reason {
+    /// No specific reason is given.
+    #[default]
+    Unspecified,
+    /// An OS signal is received, carrying the raw signal number.
+    Signal(i32),
+    /// The process is requested to exit with the code.
+    Code(i32),
+    /// A human readable message, e.g. an admin request or a config reload.
+    Message(Arc<str>),
+    /// A fatal error occurred.
+    Error(Arc<dyn Error + Send + Sync>),
+}
+
+impl Reason {
+    /// Create a reason from an error.
+    pub fn error(err: impl Error + Send + Sync + 'static) -> Self {
+        Self::Error(Arc::new(err))
+    }
+
+    /// Map this reason to a process exit code.
+    ///
+    /// Signals are mapped to `128 + signal` by shell convention, and errors to `1`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Unspecified | Self::Message(_) => 0,
+            Self::Signal(x) => 128 + x,
+            Self::Code(x) => *x,
+            Self::Error(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unspecified => write!(f, "unspecified"),
+            Self::Signal(x) => write!(f, "received signal {x}"),
+            Self::Code(x) => write!(f, "exit with code {x}"),
+            Self::Message(x) => write!(f, "{x}"),
+            Self::Error(x) => write!(f, "error: {x}"),
+        }
+    }
+}
+
+impl From<&str> for Reason {
+    fn from(value: &str) -> Self {
+        Self::Message(value.into())
+    }
+}
+
+impl From<String> for Reason {
+    fn from(value: String) -> Self {
+        Self::Message(value.into())
+    }
+}
@@ -0,0 +1,67 @@
+use std::{any::Any, time::Duration};
+
+/// Report of a shutdown.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct ShutdownReport {
+    /// Names of the handles shut down in shutdown order, `None` for anonymous handles.
+    pub handles: Vec<Option<String>>,
+    /// Hooks that have run, in completion order.
+    pub hooks: Vec<HookRun>,
+    /// Hooks that panicked.
+    pub panics: Vec<HookPanic>,
+    /// Number of asynchronous hooks aborted by [`crate::ShutUp::force`].
+    pub aborted: usize,
+    /// Whether the shutdown is escalated to [`crate::Phase::Forced`] due to a timeout.
+    pub timed_out: bool,
+    /// Number of tasks tracked by [`crate::ShutUp::track`] still alive when the report is made.
+    pub tasks: usize,
+    /// Time taken by the shutdown.
+    pub duration: Duration,
+}
+
+impl ShutdownReport {
+    /// Check whether the shutdown completed without failures.
+    pub fn is_ok(&self) -> bool {
+        self.panics.is_empty() && self.aborted == 0 && !self.timed_out
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.handles.extend(other.handles);
+        self.hooks.extend(other.hooks);
+        self.panics.extend(other.panics);
+        self.aborted += other.aborted;
+    }
+}
+
+/// A hook that has run.
+#[derive(Clone, Debug)]
+pub struct HookRun {
+    /// Name of the handle the hook is registered on, if any.
+    pub handle: Option<String>,
+    /// Name of the hook, if any.
+    pub hook: Option<String>,
+    /// Whether the hook is asynchronous.
+    pub asynchronous: bool,
+    /// Time taken by the hook.
+    pub duration: Duration,
+}
+
+/// A panic caught from a hook.
+#[derive(Clone, Debug)]
+pub struct HookPanic {
+    /// Name of the hook, if any.
+    pub hook: Option<String>,
+    /// Panic message, if the payload is a string.
+    pub message: Option<String>,
+}
+
+impl HookPanic {
+    pub(crate) fn new(hook: Option<String>, payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(x) => Some(*x),
+            Err(x) => x.downcast_ref::<&str>().map(|x| x.to_string()),
+        };
+        Self { hook, message }
+    }
+}
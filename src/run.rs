@@ -0,0 +1,62 @@
+use std::{future::poll_fn, pin::pin, time::Duration};
+
+use crate::ROOT;
+
+/// Default hard deadline of [`run_until_shutdown`].
+pub const DEFAULT_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Same as [`run_until_shutdown_timeout`] with [`DEFAULT_DEADLINE`].
+pub async fn run_until_shutdown(main: impl Future<Output = ()>) -> i32 {
+    run_until_shutdown_timeout(main, DEFAULT_DEADLINE).await
+}
+
+/// Drive the application until [`ROOT`] is shut down and drained, returning the process exit code.
+///
+/// [`ROOT`] is shut down when `main` completes, and `main` keeps being polled during shutdown.
+/// After shutdown begins, the asynchronous hooks and tracked tasks of [`ROOT`] are given `deadline` to finish,
+/// otherwise shutdown is escalated by [`crate::ShutUp::force`].
+///
+/// The exit code is given by [`crate::Reason::exit_code`],
+/// or `1` if the deadline is exceeded while the reason maps to `0`.
+/// Must be called within a Tokio runtime.
+///
+/// ```no_run
+/// # async fn app() {}
+/// # async fn run() {
+/// let code = shutup::run_until_shutdown(app()).await;
+/// std::process::exit(code);
+/// # }
+/// ```
+pub async fn run_until_shutdown_timeout(main: impl Future<Output = ()>, deadline: Duration) -> i32 {
+    let main = async {
+        main.await;
+        ROOT.shut();
+    };
+    let teardown = async {
+        ROOT.wait().await;
+        let teardown = async {
+            ROOT.shutdown().await;
+            ROOT.drained().await;
+        };
+        tokio::time::timeout(deadline, teardown).await.is_err()
+    };
+    let mut main = Some(pin!(main));
+    let mut teardown = pin!(teardown);
+    // keep polling `main` until it completes, but return as soon as teardown completes
+    let timed_out = poll_fn(|cx| {
+        if let Some(x) = &mut main
+            && x.as_mut().poll(cx).is_ready()
+        {
+            main = None;
+        }
+        teardown.as_mut().poll(cx)
+    })
+    .await;
+    if timed_out {
+        ROOT.force();
+    }
+    match ROOT.reason().unwrap_or_default().exit_code() {
+        0 if timed_out => 1,
+        x => x,
+    }
+}
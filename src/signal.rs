@@ -0,0 +1,106 @@
+//! Integration with OS signals.
+//!
+//! Requires the `signal` feature and a Tokio runtime with IO enabled.
+
+use std::{future::poll_fn, io, process, task::Poll};
+
+pub use tokio::signal::unix::SignalKind;
+use tokio::signal::unix::signal;
+
+use crate::{Phase, ROOT, Reason, ShutUp, Wait};
+
+/// A set of OS signals that trigger shutdown.
+///
+/// The default set contains `SIGINT`, `SIGTERM` and `SIGHUP`.
+#[derive(Clone, Debug)]
+pub struct Signals {
+    kinds: Vec<SignalKind>,
+    force: Option<i32>,
+}
+
+impl Signals {
+    /// Create an empty signal set.
+    pub fn empty() -> Self {
+        Self {
+            kinds: vec![],
+            force: None,
+        }
+    }
+
+    /// Add a signal to this set.
+    pub fn with(mut self, kind: SignalKind) -> Self {
+        if !self.kinds.contains(&kind) {
+            self.kinds.push(kind);
+        }
+        self
+    }
+
+    /// Exit the process immediately with `code` if a signal is received after shutdown has begun,
+    /// including during the pre-stop delay.
+    ///
+    /// Without this policy, signals received after shutdown are ignored.
+    pub fn force_exit(mut self, code: i32) -> Self {
+        self.force = Some(code);
+        self
+    }
+}
+
+impl Default for Signals {
+    fn default() -> Self {
+        Self::empty()
+            .with(SignalKind::interrupt())
+            .with(SignalKind::terminate())
+            .with(SignalKind::hangup())
+    }
+}
+
+impl ShutUp {
+    /// Shut down this handle when any of the given signals is received.
+    ///
+    /// Signal listeners are installed immediately,
+    /// but signals are only handled while the returned future is being polled, so it should be spawned.
+    /// Note that once installed, the default action of these signals no longer applies to the process.
+    pub fn listen(&self, signals: Signals) -> io::Result<impl Wait + use<>> {
+        let mut listeners = signals
+            .kinds
+            .into_iter()
+            .map(|kind| Ok((kind, signal(kind)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        let handle = self.clone();
+        let force = signals.force;
+        Ok(async move {
+            loop {
+                let received = poll_fn(|cx| {
+                    for (kind, i) in &mut listeners {
+                        if let Poll::Ready(x) = i.poll_recv(cx) {
+                            return Poll::Ready(x.map(|_| *kind));
+                        }
+                    }
+                    Poll::Pending
+                })
+                .await;
+                let Some(kind) = received else {
+                    return;
+                };
+                let reason = Reason::Signal(kind.as_raw_value());
+                // shutdown may still be waiting for the pre-stop delay
+                if let Some(code) = force
+                    && handle.phase() != Phase::Running
+                {
+                    process::exit(code);
+                }
+                handle.shut_with(reason);
+                if force.is_none() {
+                    return;
+                }
+            }
+        })
+    }
+}
+
+/// Shut down [`ROOT`] when any of the default [`Signals`] is received.
+///
+/// See [`ShutUp::listen`].
+pub fn listen() -> io::Result<impl Wait> {
+    ROOT.listen(Signals::default())
+}
@@ -0,0 +1,253 @@
+use std::{pin::Pin, sync::atomic::Ordering};
+
+use crate::{
+    Generation, Phase, ShutUp, Wait,
+    util::{join_all, lock, race},
+};
+
+/// Lifecycle state of a shutdown handle.
+///
+/// States are ordered, a handle only moves forward from [`State::Running`] to [`State::Terminated`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum State {
+    /// The handle is not shut down.
+    Running,
+    /// The handle is shut down, tasks may still be running.
+    ShuttingDown,
+    /// The handle is shut down and all tracked tasks of it and its children have exited.
+    Drained,
+    /// The handle is drained and no asynchronous hooks of it and its children are left to run,
+    /// either because they have been run by [`ShutUp::shutdown`] or because none is registered.
+    Terminated,
+}
+
+impl State {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Running,
+            1 => Self::ShuttingDown,
+            2 => Self::Drained,
+            _ => Self::Terminated,
+        }
+    }
+}
+
+/// Receiver observing state transitions of a shutdown handle, similar to a watch channel.
+///
+/// Created by [`ShutUp::watch`].
+/// Transitions happening in quick succession may be observed as a single one.
+/// A reset of the handle, see [`ShutUp::reset`], is observed as a change as well.
+pub struct StateWatch {
+    handle: ShutUp,
+    generation: u64,
+    seen: State,
+}
+
+impl StateWatch {
+    /// Get the current state without marking it as seen.
+    pub fn borrow(&self) -> State {
+        self.handle.state()
+    }
+
+    /// Get the current state and mark it as seen.
+    pub fn borrow_and_update(&mut self) -> State {
+        let generation = self.handle.current();
+        self.generation = generation.id;
+        self.seen = self.handle.state_in(&generation);
+        self.seen
+    }
+
+    /// Check whether the state has changed since it was last seen.
+    pub fn has_changed(&self) -> bool {
+        let generation = self.handle.current();
+        generation.id != self.generation || self.handle.state_in(&generation) > self.seen
+    }
+
+    /// Wait until the state changes from the last seen one, then mark the new state as seen.
+    ///
+    /// Returns `None` if [`State::Terminated`] has been seen and the handle has not been reset since,
+    /// as no more transitions would happen until then.
+    pub async fn changed(&mut self) -> Option<State> {
+        let generation = self.handle.current();
+        if generation.id != self.generation {
+            return Some(self.borrow_and_update());
+        }
+        let next = match self.seen {
+            State::Running => State::ShuttingDown,
+            State::ShuttingDown => State::Drained,
+            State::Drained => State::Terminated,
+            State::Terminated => return None,
+        };
+        race(
+            self.handle.wait_state(&generation, next),
+            generation.retired.wait(),
+        )
+        .await;
+        Some(self.borrow_and_update())
+    }
+}
+
+impl ShutUp {
+    /// Get the current lifecycle state of this handle.
+    ///
+    /// The state starts over from [`State::Running`] when this handle is reset, see [`ShutUp::reset`].
+    pub fn state(&self) -> State {
+        self.state_in(&self.current())
+    }
+
+    fn state_in(&self, generation: &Generation) -> State {
+        let off = generation.signal.is_set();
+        let drained = off && self.alive_tasks_in(generation) == 0;
+        let state = if drained && self.hooks_done(generation) {
+            State::Terminated
+        } else if drained {
+            State::Drained
+        } else if off {
+            State::ShuttingDown
+        } else {
+            State::Running
+        };
+        // tasks may be tracked after draining, keep the state moving forward only
+        let last = generation.state.fetch_max(state as u8, Ordering::AcqRel);
+        State::from_u8(last.max(state as u8))
+    }
+
+    /// Observe state transitions of this handle.
+    ///
+    /// The current state is marked as seen.
+    pub fn watch(&self) -> StateWatch {
+        let generation = self.current();
+        StateWatch {
+            handle: self.clone(),
+            generation: generation.id,
+            seen: self.state_in(&generation),
+        }
+    }
+
+    /// Wait until the given generation of this handle reaches the state.
+    fn wait_state(&self, generation: &Generation, state: State) -> impl Wait + use<> {
+        let wait = self.wait_in(generation, Phase::Graceful);
+        let drained = (state >= State::Drained).then(|| self.drain_in(generation));
+        let completed = (state >= State::Terminated).then(|| self.hooks_run(generation));
+        async move {
+            wait.await;
+            if let Some(drained) = drained {
+                drained.await;
+            }
+            if let Some(completed) = completed {
+                completed.await;
+            }
+        }
+    }
+
+    /// Check whether the given generation of this handle has run or has no asynchronous hooks,
+    /// and so do its children.
+    fn hooks_done(&self, generation: &Generation) -> bool {
+        if generation.completed.is_set() {
+            return true;
+        }
+        if generation.claimed.load(Ordering::Acquire) || !lock(&generation.async_hooks).is_empty() {
+            return false;
+        }
+        self.children().iter().all(|i| i.hooks_done(&i.current()))
+    }
+
+    /// Wait until [`Self::hooks_done`] holds.
+    fn hooks_run(&self, generation: &Generation) -> Pin<Box<dyn Wait>> {
+        if generation.claimed.load(Ordering::Acquire) || !lock(&generation.async_hooks).is_empty() {
+            return Box::pin(generation.completed.wait());
+        }
+        let children = self.children();
+        let children = children.iter().map(|i| i.hooks_run(&i.current())).collect();
+        let completed = generation.completed.wait();
+        Box::pin(race(completed, async move {
+            join_all(children).await;
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future, time::Instant};
+
+    use super::*;
+    use crate::{latch::Latch, util::block_on};
+
+    #[test]
+    fn watch_observes_reset() {
+        let handle = ShutUp::new();
+        handle.register_async_hook(future::pending());
+        let mut watch = handle.watch();
+        handle.shut();
+        assert_eq!(watch.borrow_and_update(), State::Drained);
+        let mut changed = Box::pin(watch.changed());
+        assert!(block_on(changed.as_mut(), Some(Instant::now())).is_none());
+        assert!(handle.reset());
+        assert_eq!(block_on(changed, None), Some(Some(State::Running)));
+        assert!(!watch.has_changed());
+    }
+
+    #[test]
+    fn states_without_hooks_or_tasks() {
+        let handle = ShutUp::new();
+        let mut watch = handle.watch();
+        assert_eq!(watch.borrow(), State::Running);
+        handle.shut();
+        // nothing is left to wait for, so the transitions are observed as one
+        assert_eq!(
+            block_on(watch.changed(), None),
+            Some(Some(State::Terminated))
+        );
+        assert_eq!(block_on(watch.changed(), None), Some(None));
+    }
+
+    #[test]
+    fn states_with_tasks() {
+        let handle = ShutUp::new();
+        let task = handle.track(future::pending::<()>());
+        handle.shut();
+        assert_eq!(handle.state(), State::ShuttingDown);
+        drop(task);
+        assert_eq!(handle.state(), State::Terminated);
+    }
+
+    #[test]
+    fn states_with_async_hooks() {
+        let handle = ShutUp::new();
+        handle.register_async_hook(async {});
+        handle.shut();
+        assert_eq!(handle.state(), State::Drained);
+        block_on(handle.shutdown(), None);
+        assert_eq!(handle.state(), State::Terminated);
+    }
+
+    #[test]
+    fn states_with_tasks_and_async_hooks() {
+        let handle = ShutUp::new();
+        let child = handle.child();
+        let task = child.track(future::pending::<()>());
+        let release = Latch::new();
+        child.register_async_hook(release.wait());
+        let mut watch = handle.watch();
+        handle.shut();
+        assert_eq!(
+            block_on(watch.changed(), None),
+            Some(Some(State::ShuttingDown))
+        );
+        let mut changed = Box::pin(watch.changed());
+        assert!(block_on(changed.as_mut(), Some(Instant::now())).is_none());
+        drop(task);
+        assert_eq!(block_on(changed, None), Some(Some(State::Drained)));
+        let mut shutdown = Box::pin(handle.shutdown());
+        assert!(block_on(shutdown.as_mut(), Some(Instant::now())).is_none());
+        assert_eq!(handle.state(), State::Drained);
+        release.set();
+        block_on(shutdown, None);
+        assert_eq!(
+            block_on(watch.changed(), None),
+            Some(Some(State::Terminated))
+        );
+        assert_eq!(child.state(), State::Terminated);
+    }
+}
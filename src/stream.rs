@@ -0,0 +1,87 @@
+//! Stream adapters terminating on shutdown.
+//!
+//! Requires the `futures` feature.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::{ShutUp, Wait};
+
+/// Policy of handling remaining items of a stream after shutdown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Drain {
+    /// End the stream immediately.
+    #[default]
+    Stop,
+    /// Keep yielding items that are immediately ready, end the stream once it would block.
+    Ready,
+    /// Keep yielding items until the stream ends by itself.
+    All,
+}
+
+/// Stream for [`ShutUpStreamExt::take_until_shutdown`].
+#[must_use = "streams do nothing unless polled"]
+pub struct TakeUntilShutdown<S> {
+    stream: Pin<Box<S>>,
+    /// `None` once the handle is shut down.
+    wait: Option<Pin<Box<dyn Wait>>>,
+    drain: Drain,
+    done: bool,
+}
+
+impl<S> TakeUntilShutdown<S> {
+    /// Set the policy of handling remaining items after shutdown.
+    pub fn drain(mut self, drain: Drain) -> Self {
+        self.drain = drain;
+        self
+    }
+}
+
+impl<S: Stream> Stream for TakeUntilShutdown<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        if let Some(wait) = &mut self.wait
+            && wait.as_mut().poll(cx).is_ready()
+        {
+            self.wait = None;
+        }
+        let next = match (&self.wait, self.drain) {
+            (None, Drain::Stop) => Poll::Ready(None),
+            (None, Drain::Ready) => match self.stream.as_mut().poll_next(cx) {
+                Poll::Pending => Poll::Ready(None),
+                x => x,
+            },
+            _ => self.stream.as_mut().poll_next(cx),
+        };
+        if let Poll::Ready(None) = next {
+            self.done = true;
+        }
+        next
+    }
+}
+
+/// Extension methods for streams terminating on shutdown.
+pub trait ShutUpStreamExt: Stream + Sized {
+    /// End this stream once the handle is shut down.
+    ///
+    /// Remaining items are handled according to [`Drain::Stop`] by default,
+    /// see [`TakeUntilShutdown::drain`].
+    fn take_until_shutdown(self, handle: &ShutUp) -> TakeUntilShutdown<Self> {
+        TakeUntilShutdown {
+            stream: Box::pin(self),
+            wait: Some(Box::pin(handle.wait())),
+            drain: Drain::default(),
+            done: false,
+        }
+    }
+}
+
+impl<S: Stream> ShutUpStreamExt for S {}
@@ -0,0 +1,325 @@
+use std::{
+    collections::VecDeque,
+    future::poll_fn,
+    pin::{Pin, pin},
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinHandle;
+
+use crate::{IntensityExceeded, Reason, ShutUp};
+
+/// Strategy of restarting the children of a [`Supervisor`] when one of them exits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Restart only the exited child.
+    #[default]
+    OneForOne,
+    /// Restart all the children.
+    OneForAll,
+    /// Restart the exited child and the children started after it.
+    RestForOne,
+}
+
+type Factory = Box<dyn Fn(ShutUp) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+struct Child {
+    name: String,
+    factory: Factory,
+    handle: Option<ShutUp>,
+    task: Option<JoinHandle<Option<()>>>,
+}
+
+/// An Erlang-style supervisor restarting the child tasks of a shutdown handle.
+///
+/// Each child is run with a child handle named after it, see [`ShutUp::supervise`].
+/// A child exiting or panicking while the supervising handle is running is considered failed,
+/// and is restarted along with its siblings according to the [`Strategy`].
+/// Children to be restarted are shut down in reverse start order,
+/// their handles are reset, see [`ShutUp::reset`], and they are then started again in start order.
+///
+/// If more than [`Self::intensity`] restarts happen within the period,
+/// the supervising handle is shut down with [`IntensityExceeded`] as the reason.
+pub struct Supervisor {
+    strategy: Strategy,
+    max_restarts: usize,
+    period: Duration,
+    timeout: Duration,
+    children: Vec<Child>,
+}
+
+impl Supervisor {
+    /// Create a supervisor without children,
+    /// allowing `1` restart in `5` seconds and waiting `5` seconds for children to shut down.
+    pub fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            max_restarts: 1,
+            period: Duration::from_secs(5),
+            timeout: Duration::from_secs(5),
+            children: vec![],
+        }
+    }
+
+    /// Allow at most `max_restarts` restarts within `period`.
+    pub fn intensity(mut self, max_restarts: usize, period: Duration) -> Self {
+        self.max_restarts = max_restarts;
+        self.period = period;
+        self
+    }
+
+    /// Set how long children to be restarted are given to shut down before being forced.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Add a child, started by calling `factory` with its handle.
+    ///
+    /// Children are started in the order they are added.
+    pub fn child<F>(
+        mut self,
+        name: impl Into<String>,
+        factory: impl Fn(ShutUp) -> F + Send + Sync + 'static,
+    ) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.children.push(Child {
+            name: name.into(),
+            factory: Box::new(move |x| Box::pin(factory(x))),
+            handle: None,
+            task: None,
+        });
+        self
+    }
+
+    async fn run(mut self, handle: ShutUp) {
+        let mut restarts = VecDeque::new();
+        for i in &mut self.children {
+            i.handle = Some(handle.child_named(&i.name));
+            i.start();
+        }
+        let mut shutdown = pin!(handle.wait());
+        loop {
+            // check shutdown first, so that children stopped by it are not restarted
+            let exited = poll_fn(|cx| {
+                if shutdown.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(None);
+                }
+                for (n, i) in self.children.iter_mut().enumerate() {
+                    if let Some(task) = &mut i.task
+                        && let Poll::Ready(result) = Pin::new(task).poll(cx)
+                    {
+                        i.task = None;
+                        return Poll::Ready(Some((n, result)));
+                    }
+                }
+                Poll::Pending
+            })
+            .await;
+            let Some((n, result)) = exited else {
+                break;
+            };
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                handle = handle.display_name(),
+                child = self.children[n].name,
+                panicked = result.as_ref().is_err_and(|x| x.is_panic()),
+                "supervised child exited"
+            );
+            drop(result);
+            let now = Instant::now();
+            restarts.push_back(now);
+            while restarts.front().is_some_and(|x| now - *x > self.period) {
+                restarts.pop_front();
+            }
+            if restarts.len() > self.max_restarts {
+                #[cfg(feature = "tracing")]
+                tracing::error!(
+                    handle = handle.display_name(),
+                    "restart intensity exceeded, escalating"
+                );
+                handle.shut_with(Reason::error(IntensityExceeded));
+                break;
+            }
+            let range = match self.strategy {
+                Strategy::OneForOne => n..n + 1,
+                Strategy::OneForAll => 0..self.children.len(),
+                Strategy::RestForOne => n..self.children.len(),
+            };
+            let children = &mut self.children[range];
+            for i in children.iter_mut().rev() {
+                i.stop(self.timeout).await;
+            }
+            for i in children {
+                #[cfg(feature = "tracing")]
+                tracing::info!(
+                    handle = handle.display_name(),
+                    child = i.name,
+                    "restarting supervised child"
+                );
+                i.handle.as_ref().unwrap().reset();
+                i.start();
+            }
+        }
+        // children are shut down along with the supervising handle
+        for i in self.children {
+            if let Some(task) = i.task {
+                let _ = task.await;
+            }
+        }
+    }
+}
+
+impl Child {
+    fn start(&mut self) {
+        let handle = self.handle.as_ref().unwrap();
+        self.task = Some(handle.spawn((self.factory)(handle.clone())));
+    }
+
+    /// Shut down this child, forcing it after `timeout`, and wait for its task to exit.
+    async fn stop(&mut self, timeout: Duration) {
+        let handle = self.handle.as_ref().unwrap();
+        let task = self.task.take();
+        // the task may ignore shutdown even if the asynchronous hooks complete in time
+        let mut stop = pin!(async move {
+            handle.shutdown().await;
+            if let Some(task) = task {
+                let _ = task.await;
+            }
+        });
+        if tokio::time::timeout(timeout, stop.as_mut()).await.is_err() {
+            handle.force();
+            stop.await;
+        }
+    }
+}
+
+impl ShutUp {
+    /// Run the supervisor on the current Tokio runtime, with this handle as the supervising handle.
+    ///
+    /// The supervisor stops once this handle is shut down and all the children have exited.
+    /// See [`Supervisor`].
+    pub fn supervise(&self, supervisor: Supervisor) -> JoinHandle<Option<()>> {
+        self.spawn(supervisor.run(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::Phase;
+
+    /// Start counts of children `a`, `b` and `c`, where `b` fails once shortly after its first start.
+    async fn restarts(strategy: Strategy) -> [usize; 3] {
+        let handle = ShutUp::new();
+        let starts: Arc<[AtomicUsize; 3]> = Arc::default();
+        let mut supervisor = Supervisor::new(strategy);
+        for (n, name) in ["a", "b", "c"].into_iter().enumerate() {
+            let starts = starts.clone();
+            supervisor = supervisor.child(name, move |handle: ShutUp| {
+                let first = starts[n].fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    if n == 1 && first {
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        return;
+                    }
+                    handle.wait().await;
+                }
+            });
+        }
+        let task = handle.supervise(supervisor);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!handle.off());
+        handle.shut();
+        task.await.unwrap();
+        starts.each_ref().map(|x| x.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn one_for_one() {
+        assert_eq!(restarts(Strategy::OneForOne).await, [1, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn one_for_all() {
+        assert_eq!(restarts(Strategy::OneForAll).await, [2, 2, 2]);
+    }
+
+    #[tokio::test]
+    async fn rest_for_one() {
+        assert_eq!(restarts(Strategy::RestForOne).await, [1, 2, 2]);
+    }
+
+    #[tokio::test]
+    async fn panicking_child_is_restarted() {
+        let handle = ShutUp::new();
+        let starts = Arc::new(AtomicUsize::new(0));
+        let counter = starts.clone();
+        let task = handle.supervise(Supervisor::new(Strategy::OneForOne).child(
+            "flaky",
+            move |handle: ShutUp| {
+                let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    if first {
+                        panic!("boom");
+                    }
+                    handle.wait().await;
+                }
+            },
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+        handle.shut();
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn intensity_exceeded_shuts_down_parent() {
+        let handle = ShutUp::new();
+        let starts = Arc::new(AtomicUsize::new(0));
+        let counter = starts.clone();
+        let supervisor = Supervisor::new(Strategy::OneForOne)
+            .intensity(2, Duration::from_secs(60))
+            .child("crashing", move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async {}
+            });
+        handle.supervise(supervisor).await.unwrap();
+        assert_eq!(starts.load(Ordering::SeqCst), 3);
+        assert!(matches!(handle.reason(), Some(Reason::Error(x)) if x.is::<IntensityExceeded>()));
+    }
+
+    #[tokio::test]
+    async fn stuck_child_is_forced_on_restart() {
+        let handle = ShutUp::new();
+        let starts = Arc::new(AtomicUsize::new(0));
+        let counter = starts.clone();
+        let supervisor = Supervisor::new(Strategy::OneForAll)
+            .shutdown_timeout(Duration::from_millis(10))
+            .child("stuck", |_| std::future::pending())
+            .child("failing", move |handle: ShutUp| {
+                let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    if !first {
+                        handle.wait().await;
+                    }
+                }
+            });
+        let task = handle.supervise(supervisor);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+        assert_eq!(handle.phase(), Phase::Running);
+        // the stuck child ignores shutdown, so the supervisor only stops once forced
+        handle.shut();
+        handle.force();
+        task.await.unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use std::{pin::Pin, sync::Arc};
+
+#[cfg(feature = "tokio")]
+use tokio::task::JoinHandle;
+
+use crate::{
+    Generation, Phase, ShutUp, Wait,
+    util::{join_all, race},
+};
+
+/// Keeps a task of a handle counted as alive in the current generation until dropped.
+struct Tracked {
+    generation: Arc<Generation>,
+    /// Keeps the handle reachable from its parents while the task is alive.
+    _handle: ShutUp,
+}
+
+impl Tracked {
+    fn new(handle: &ShutUp) -> Self {
+        let generation = handle.current();
+        generation.tasks.increment();
+        Self {
+            generation,
+            _handle: handle.clone(),
+        }
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.generation.tasks.decrement();
+    }
+}
+
+impl ShutUp {
+    /// Bind a future to this handle, regardless of the async runtime it is run on.
+    ///
+    /// The future is tracked by [`Self::drained`] from now on until it is dropped,
+    /// and aborted with `None` as output once this handle reaches [`Phase::Forced`].
+    pub fn track<F: Future>(&self, fut: F) -> impl Future<Output = Option<F::Output>> + use<F> {
+        let tracked = Tracked::new(self);
+        let forced = self.wait_for(Phase::Forced);
+        async move {
+            let _tracked = tracked;
+            race(async { Some(fut.await) }, async {
+                forced.await;
+                None
+            })
+            .await
+        }
+    }
+
+    /// Spawn a task bound to this handle on the current Tokio runtime.
+    ///
+    /// See [`Self::track`].
+    #[cfg(feature = "tokio")]
+    pub fn spawn<F>(&self, fut: F) -> JoinHandle<Option<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        tokio::spawn(self.track(fut))
+    }
+
+    /// Wait until this handle is shut down and all tasks tracked by [`Self::track`]
+    /// on this handle and its children have exited.
+    pub fn drained(&self) -> impl Wait + use<> {
+        self.drain()
+    }
+
+    fn drain(&self) -> Pin<Box<dyn Wait>> {
+        self.drain_in(&self.current())
+    }
+
+    /// Wait until the given generation of this handle is drained.
+    pub(crate) fn drain_in(&self, generation: &Generation) -> Pin<Box<dyn Wait>> {
+        let wait = self.wait_in(generation, Phase::Graceful);
+        let tasks = generation.tasks.zero();
+        let this = self.clone();
+        Box::pin(async move {
+            wait.await;
+            tasks.await;
+            let children = this.children();
+            join_all(children.iter().map(Self::drain).collect()).await;
+        })
+    }
+
+    /// Count alive tasks tracked on this handle and its children.
+    pub(crate) fn alive_tasks(&self) -> usize {
+        self.alive_tasks_in(&self.current())
+    }
+
+    /// Count alive tasks tracked on the given generation of this handle and its children.
+    pub(crate) fn alive_tasks_in(&self, generation: &Generation) -> usize {
+        let children = self.children();
+        generation.tasks.get() + children.iter().map(Self::alive_tasks).sum::<usize>()
+    }
+}
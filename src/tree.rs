@@ -0,0 +1,103 @@
+use std::fmt;
+
+use crate::{Phase, ShutUp, util::lock};
+
+/// Snapshot of a shutdown handle and its descendants.
+///
+/// Created by [`ShutUp::tree`], printable as an indented tree.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Tree {
+    /// Name of the handle, if any.
+    pub name: Option<String>,
+    /// Phase of the handle.
+    pub phase: Phase,
+    /// Number of pending synchronous hooks.
+    pub hooks: usize,
+    /// Number of pending asynchronous hooks.
+    pub async_hooks: usize,
+    /// Names of asynchronous hooks being run, `None` for anonymous ones.
+    pub running: Vec<Option<String>>,
+    /// Number of alive tasks tracked on the handle, excluding its children.
+    pub tasks: usize,
+    /// Alive children of the handle.
+    pub children: Vec<Tree>,
+}
+
+impl Tree {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let name = self.name.as_deref().unwrap_or("<anonymous>");
+        write!(
+            f,
+            "{:indent$}{name} [{:?}] hooks: {}, async hooks: {}, tasks: {}, children: {}",
+            "",
+            self.phase,
+            self.hooks,
+            self.async_hooks,
+            self.tasks,
+            self.children.len(),
+            indent = depth * 2,
+        )?;
+        if !self.running.is_empty() {
+            let running = self
+                .running
+                .iter()
+                .map(|i| i.as_deref().unwrap_or("<anonymous>"))
+                .collect::<Vec<_>>();
+            write!(f, ", running: {}", running.join(", "))?;
+        }
+        writeln!(f)?;
+        for i in &self.children {
+            i.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl ShutUp {
+    /// Take a snapshot of this handle and its descendants.
+    ///
+    /// Useful for finding out which subsystem is stuck when shutdown stalls.
+    pub fn tree(&self) -> Tree {
+        let generation = self.current();
+        Tree {
+            name: self.name().map(String::from),
+            phase: self.phase(),
+            hooks: lock(&generation.hooks).len(),
+            async_hooks: lock(&generation.async_hooks).len(),
+            running: lock(&generation.running).clone(),
+            tasks: generation.tasks.get(),
+            children: self.children().iter().map(Self::tree).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future, time::Instant};
+
+    use crate::{ShutUp, util::block_on};
+
+    #[test]
+    fn running_async_hooks() {
+        let handle = ShutUp::new();
+        handle.register_async_hook_named("flush", future::pending());
+        handle.register_async_hook(future::pending());
+        let mut shutdown = Box::pin(handle.shutdown());
+        assert!(block_on(shutdown.as_mut(), Some(Instant::now())).is_none());
+        let tree = handle.tree();
+        assert_eq!(tree.async_hooks, 0);
+        assert_eq!(tree.running, [Some("flush".into()), None]);
+        assert!(tree.to_string().contains("running: flush, <anonymous>"));
+        handle.force();
+        let report = block_on(shutdown, None).unwrap();
+        assert_eq!(report.aborted, 2);
+        assert!(handle.tree().running.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    any::Any,
+    future::poll_fn,
+    panic::{self, AssertUnwindSafe},
+    pin::{Pin, pin},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::Instant,
+};
+
+/// Wait for all the futures to complete, collecting their outputs in order.
+pub(crate) async fn join_all<F: Future + Unpin>(futs: Vec<F>) -> Vec<F::Output> {
+    let mut futs = futs.into_iter().map(Some).collect::<Vec<_>>();
+    let mut outputs = futs.iter().map(|_| None).collect::<Vec<_>>();
+    poll_fn(|cx| {
+        let mut pending = false;
+        for (fut, output) in futs.iter_mut().zip(&mut outputs) {
+            let Some(x) = fut else {
+                continue;
+            };
+            match Pin::new(x).poll(cx) {
+                Poll::Ready(x) => {
+                    *output = Some(x);
+                    *fut = None;
+                }
+                Poll::Pending => pending = true,
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+    outputs.into_iter().map(Option::unwrap).collect()
+}
+
+/// Wait for either of the futures to complete, preferring the first one.
+pub(crate) async fn race<T>(a: impl Future<Output = T>, b: impl Future<Output = T>) -> T {
+    let mut a = pin!(a);
+    let mut b = pin!(b);
+    poll_fn(|cx| {
+        if let Poll::Ready(x) = a.as_mut().poll(cx) {
+            return Poll::Ready(x);
+        }
+        b.as_mut().poll(cx)
+    })
+    .await
+}
+
+/// Wait for the future to complete, catching panics.
+pub(crate) async fn catch_unwind<F: Future>(fut: F) -> Result<F::Output, Box<dyn Any + Send>> {
+    let mut fut = pin!(fut);
+    poll_fn(
+        |cx| match panic::catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx))) {
+            Ok(Poll::Ready(x)) => Poll::Ready(Ok(x)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(err) => Poll::Ready(Err(err)),
+        },
+    )
+    .await
+}
+
+/// Lock the mutex, ignoring poisoning.
+///
+/// No lock is held while running hooks, so data behind a poisoned mutex is still consistent.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Block the current thread on the future until it completes or the deadline is reached.
+///
+/// Returns `None` if the deadline is reached first.
+pub(crate) fn block_on<F: Future>(fut: F, deadline: Option<Instant>) -> Option<F::Output> {
+    let mut fut = pin!(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(x) = fut.as_mut().poll(&mut cx) {
+            return Some(x);
+        }
+        match deadline {
+            None => thread::park(),
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return None;
+                }
+                thread::park_timeout(deadline - now);
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Kept in its own test binary, as other tests create children of [`ROOT`] concurrently.
+
+use std::time::Duration;
+
+use shutup::{ROOT, ShutUp};
+
+#[test]
+fn dropped_handles_leave_root() {
+    let before = ROOT.tree().children.len();
+    let handles = (0..8).map(|_| ShutUp::new()).collect::<Vec<_>>();
+    let waiters = handles.iter().map(ShutUp::wait).collect::<Vec<_>>();
+    for i in &handles {
+        assert!(!i.wait_timeout(Duration::ZERO));
+    }
+    assert_eq!(ROOT.tree().children.len(), before + 8);
+    drop(handles);
+    // waiters keep their handles alive
+    assert_eq!(ROOT.tree().children.len(), before + 8);
+    drop(waiters);
+    assert_eq!(ROOT.tree().children.len(), before);
+}